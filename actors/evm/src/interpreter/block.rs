@@ -0,0 +1,37 @@
+use cid::Cid;
+use fvm_shared::address::Address;
+
+use crate::interpreter::U256;
+
+/// The EVM's network id; distinct from the Filecoin chain id and fixed for
+/// now, same as `fvm_shared`'s own chain-id constant for the EVM-compatible
+/// RPC surface.
+pub const CHAIN_ID: u64 = 314;
+
+/// Block/chain context the interpreter needs to service `BLOCKHASH`,
+/// `NUMBER`, `TIMESTAMP`, `COINBASE`, `CHAINID` and `BASEFEE`, modeled the
+/// same way as the storage interface: a small accessor trait `System`
+/// implements over whatever the FVM `Runtime` exposes, so opcode handlers
+/// never touch `Runtime` directly.
+pub trait BlockContext {
+    /// The current epoch, i.e. Ethereum's block `NUMBER`.
+    fn block_height(&self) -> u64;
+    /// The tipset timestamp at `height`, or `0` if it isn't the current
+    /// height (the FVM doesn't expose historical timestamps).
+    fn timestamp(&self, height: u64) -> u64;
+    /// The tipset CID at `height`, reinterpreted as a 32-byte hash, or zero
+    /// if `height` is not within the last 256 blocks (per `BLOCKHASH`'s
+    /// window) or is not yet produced.
+    fn block_hash(&self, height: u64) -> U256;
+    /// Stand-in for `COINBASE`: the FVM has no miner-address concept
+    /// meaningful to an EVM contract, so this is the system actor address.
+    fn coinbase(&self) -> Address;
+    fn chain_id(&self) -> U256;
+    /// The network's base fee for the current epoch, in attoFIL.
+    fn base_fee(&self) -> U256;
+}
+
+pub(crate) fn cid_to_u256(cid: &Cid) -> U256 {
+    let digest = cid.hash().digest();
+    U256::from_big_endian(digest)
+}
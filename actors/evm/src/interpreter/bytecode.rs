@@ -0,0 +1,42 @@
+/// Parsed EVM bytecode: the raw instruction stream plus the set of valid
+/// `JUMPDEST` offsets, computed once up front so the interpreter loop never
+/// has to re-scan the program to validate a jump.
+pub struct Bytecode {
+    code: Vec<u8>,
+    valid_jump_destinations: Vec<bool>,
+}
+
+const OP_JUMPDEST: u8 = 0x5b;
+const OP_PUSH1: u8 = 0x60;
+const OP_PUSH32: u8 = 0x7f;
+
+impl Bytecode {
+    pub fn new(code: &[u8]) -> Result<Self, String> {
+        let mut valid_jump_destinations = vec![false; code.len()];
+
+        let mut i = 0;
+        while i < code.len() {
+            let op = code[i];
+            if op == OP_JUMPDEST {
+                valid_jump_destinations[i] = true;
+                i += 1;
+            } else if (OP_PUSH1..=OP_PUSH32).contains(&op) {
+                // Skip over push immediates; they can never be valid jump
+                // targets even if their bytes happen to look like JUMPDEST.
+                i += 1 + (op - OP_PUSH1 + 1) as usize;
+            } else {
+                i += 1;
+            }
+        }
+
+        Ok(Bytecode { code: code.to_vec(), valid_jump_destinations })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn is_valid_jump_destination(&self, offset: usize) -> bool {
+        self.valid_jump_destinations.get(offset).copied().unwrap_or(false)
+    }
+}
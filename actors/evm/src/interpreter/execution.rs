@@ -0,0 +1,55 @@
+use bytes::Bytes;
+
+/// Mutable, per-invocation execution context threaded through the
+/// interpreter loop: the method being invoked, its input, and the
+/// interpreter's working stack/memory.
+pub struct ExecutionState {
+    /// The actor method number this invocation was dispatched under.
+    pub method: u64,
+    pub input_data: Bytes,
+    pub stack: Vec<crate::interpreter::U256>,
+    pub memory: Vec<u8>,
+    pub return_data: Bytes,
+    /// Running total of gas priced by the interpreter so far (currently:
+    /// `SLOAD`/`SSTORE` slot and `CALL` target cold/warm access per EIP-2929,
+    /// plus each precompile call's own cost). Not enforced against
+    /// a limit -- this crate has no gas-limit plumbing yet -- but tracked so
+    /// callers and tests can see what an invocation cost.
+    pub gas_used: u64,
+}
+
+impl ExecutionState {
+    pub fn new(method: u64, input_data: Bytes) -> Self {
+        ExecutionState {
+            method,
+            input_data,
+            stack: Vec::new(),
+            memory: Vec::new(),
+            return_data: Bytes::new(),
+            gas_used: 0,
+        }
+    }
+
+    pub fn charge_gas(&mut self, cost: u64) {
+        self.gas_used += cost;
+    }
+
+    /// Read `len` bytes from `offset`, zero-extending the memory buffer (per
+    /// EVM semantics: reads past the end of memory are zero, not an error).
+    pub fn memory_read(&mut self, offset: usize, len: usize) -> Vec<u8> {
+        let end = offset + len;
+        if end > self.memory.len() {
+            self.memory.resize(end, 0);
+        }
+        self.memory[offset..end].to_vec()
+    }
+
+    /// Write `data` into memory at `offset`, zero-extending as needed.
+    pub fn memory_write(&mut self, offset: usize, data: &[u8]) {
+        let end = offset + data.len();
+        if end > self.memory.len() {
+            self.memory.resize(end, 0);
+        }
+        self.memory[offset..end].copy_from_slice(data);
+    }
+}
@@ -0,0 +1,294 @@
+mod block;
+mod bytecode;
+mod execution;
+mod output;
+pub mod precompiles;
+mod system;
+mod uints;
+
+pub use block::BlockContext;
+pub use bytecode::Bytecode;
+pub use execution::ExecutionState;
+pub use output::{Log, Output, StatusCode};
+pub use precompiles::precompile;
+pub use system::{SnapshotId, Storage, System};
+pub use uints::U256;
+
+use fvm_shared::address::Address;
+
+const OP_STOP: u8 = 0x00;
+const OP_BLOCKHASH: u8 = 0x40;
+const OP_COINBASE: u8 = 0x41;
+const OP_TIMESTAMP: u8 = 0x42;
+const OP_NUMBER: u8 = 0x43;
+const OP_CHAINID: u8 = 0x46;
+const OP_BASEFEE: u8 = 0x48;
+const OP_POP: u8 = 0x50;
+const OP_SLOAD: u8 = 0x54;
+const OP_SSTORE: u8 = 0x55;
+const OP_JUMP: u8 = 0x56;
+const OP_JUMPI: u8 = 0x57;
+const OP_JUMPDEST: u8 = 0x5b;
+const OP_TLOAD: u8 = 0x5c;
+const OP_TSTORE: u8 = 0x5d;
+const OP_PUSH1: u8 = 0x60;
+const OP_PUSH32: u8 = 0x7f;
+const OP_DUP1: u8 = 0x80;
+const OP_DUP16: u8 = 0x8f;
+const OP_SWAP1: u8 = 0x90;
+const OP_SWAP16: u8 = 0x9f;
+const OP_CALL: u8 = 0xf1;
+const OP_RETURN: u8 = 0xf3;
+const OP_REVERT: u8 = 0xfd;
+const OP_SELFDESTRUCT: u8 = 0xff;
+const OP_LOG0: u8 = 0xa0;
+const OP_LOG4: u8 = 0xa4;
+
+/// Run `bytecode` to completion (or until it traps) against `state`,
+/// servicing storage opcodes and `CALL`-to-precompile dispatch through
+/// `system`.
+///
+/// This is the interpreter's single entry point; actor methods never touch
+/// the opcode loop directly.
+///
+/// Only the opcodes the actor methods and storage/rollback/precompile/log/
+/// block-context machinery actually need are implemented so far -- there is
+/// no arithmetic (`ADD`/`MUL`/...) yet. An unrecognized opcode traps the
+/// same way a stack underflow or an out-of-range jump does: as a revert
+/// with no output, consistent with how a real EVM treats `INVALID` and
+/// similarly malformed programs.
+///
+/// `CALL` only ever reaches a precompile (`0x01`-`0x05` and `0x09`; there is
+/// no native `0x06`-`0x08`, so those fail like any other address): this crate has no
+/// way to load and interpret another actor's bytecode from inside the
+/// opcode loop, so a `CALL` to any other address simply fails (pushes `0`),
+/// the same as a real EVM `CALL` that runs out of gas before the callee
+/// starts executing.
+///
+/// `SLOAD`/`SSTORE` are priced per EIP-2929: the first access to a slot in
+/// an invocation costs [`system::COLD_ACCESS_COST`], every access after that
+/// costs [`system::WARM_ACCESS_COST`]. The running total lands in
+/// `state.gas_used` / `Output::gas_used`; there is no gas limit to enforce
+/// it against yet.
+pub fn execute<BS, RT>(
+    bytecode: &Bytecode,
+    state: &mut ExecutionState,
+    system: &mut System<BS, RT>,
+) -> Result<Output, StatusCode>
+where
+    BS: fvm_ipld_blockstore::Blockstore + Clone,
+    RT: fil_actors_runtime::runtime::Runtime<BS>,
+{
+    let code = bytecode.as_slice();
+    let mut pc: usize = 0;
+
+    macro_rules! trap {
+        () => {
+            return Ok(Output {
+                status_code: StatusCode::Revert,
+                reverted: true,
+                output_data: bytes::Bytes::new(),
+                selfdestroyed: None,
+                gas_used: state.gas_used,
+            })
+        };
+    }
+
+    macro_rules! pop {
+        () => {
+            match state.stack.pop() {
+                Some(v) => v,
+                None => trap!(),
+            }
+        };
+    }
+
+    loop {
+        let Some(op) = code.get(pc).copied() else {
+            // Falling off the end of the code is equivalent to an implicit
+            // STOP.
+            return Ok(Output {
+                status_code: StatusCode::Success,
+                reverted: false,
+                output_data: bytes::Bytes::new(),
+                selfdestroyed: None,
+                gas_used: state.gas_used,
+            });
+        };
+
+        match op {
+            OP_STOP => {
+                return Ok(Output {
+                    status_code: StatusCode::Success,
+                    reverted: false,
+                    output_data: bytes::Bytes::new(),
+                    selfdestroyed: None,
+                    gas_used: state.gas_used,
+                });
+            }
+            OP_RETURN | OP_REVERT => {
+                let offset = pop!().as_usize();
+                let len = pop!().as_usize();
+                let output_data = state.memory_read(offset, len);
+                return Ok(Output {
+                    status_code: if op == OP_RETURN { StatusCode::Success } else { StatusCode::Revert },
+                    reverted: op == OP_REVERT,
+                    output_data: output_data.into(),
+                    selfdestroyed: None,
+                    gas_used: state.gas_used,
+                });
+            }
+            OP_BLOCKHASH => {
+                let height = pop!().low_u64();
+                state.stack.push(system.block_hash(height));
+            }
+            OP_COINBASE => {
+                let id = system.coinbase().id().unwrap_or(0);
+                state.stack.push(U256::from_u64(id));
+            }
+            OP_TIMESTAMP => {
+                let height = system.block_height();
+                state.stack.push(U256::from_u64(system.timestamp(height)));
+            }
+            OP_NUMBER => {
+                state.stack.push(U256::from_u64(system.block_height()));
+            }
+            OP_CHAINID => {
+                state.stack.push(system.chain_id());
+            }
+            OP_BASEFEE => {
+                state.stack.push(system.base_fee());
+            }
+            OP_POP => {
+                pop!();
+            }
+            OP_SLOAD => {
+                let key = pop!();
+                // `mark_warm` (rather than letting `get_storage` mark it as a
+                // side effect) so the cold/warm distinction it returns is
+                // available here, to charge the right EIP-2929 cost.
+                let cold = system.mark_warm(key);
+                state.charge_gas(system::access_cost(cold));
+                let value = system.get_storage(key).map_err(StatusCode::interpreter_error)?;
+                state.stack.push(value.unwrap_or(U256::ZERO));
+            }
+            OP_SSTORE => {
+                let key = pop!();
+                let value = pop!();
+                let cold = system.mark_warm(key);
+                state.charge_gas(system::access_cost(cold));
+                system.set_storage(key, value).map_err(StatusCode::interpreter_error)?;
+            }
+            OP_TLOAD => {
+                let key = pop!();
+                state.stack.push(system.get_transient_storage(key));
+            }
+            OP_TSTORE => {
+                let key = pop!();
+                let value = pop!();
+                system.set_transient_storage(key, value);
+            }
+            OP_JUMP => {
+                let target = pop!().as_usize();
+                if !bytecode.is_valid_jump_destination(target) {
+                    trap!();
+                }
+                pc = target;
+                continue;
+            }
+            OP_JUMPI => {
+                let target = pop!().as_usize();
+                let cond = pop!();
+                if !cond.is_zero() {
+                    if !bytecode.is_valid_jump_destination(target) {
+                        trap!();
+                    }
+                    pc = target;
+                    continue;
+                }
+            }
+            OP_JUMPDEST => {}
+            OP_PUSH1..=OP_PUSH32 => {
+                let n = (op - OP_PUSH1 + 1) as usize;
+                let bytes = code.get(pc + 1..(pc + 1 + n).min(code.len())).unwrap_or(&[]);
+                state.stack.push(U256::from_big_endian(bytes));
+                pc += n;
+            }
+            OP_DUP1..=OP_DUP16 => {
+                let depth = (op - OP_DUP1 + 1) as usize;
+                if state.stack.len() < depth {
+                    trap!();
+                }
+                let value = state.stack[state.stack.len() - depth];
+                state.stack.push(value);
+            }
+            OP_SWAP1..=OP_SWAP16 => {
+                let depth = (op - OP_SWAP1 + 1) as usize;
+                let len = state.stack.len();
+                if len < depth + 1 {
+                    trap!();
+                }
+                state.stack.swap(len - 1, len - 1 - depth);
+            }
+            OP_CALL => {
+                let _gas = pop!();
+                let addr = pop!();
+                let _value = pop!();
+                let args_offset = pop!().as_usize();
+                let args_len = pop!().as_usize();
+                let ret_offset = pop!().as_usize();
+                let ret_len = pop!().as_usize();
+
+                // The call target is an account access, priced and tracked
+                // through the address access-set -- not `mark_warm`, which
+                // is the storage-slot access-set and would otherwise collide
+                // with a `SLOAD`/`SSTORE` to the numerically same slot.
+                let target = Address::new_id(addr.low_u64());
+                let cold = system.mark_warm_address(target);
+                state.charge_gas(system::access_cost(cold));
+
+                let input = state.memory_read(args_offset, args_len);
+                match precompile(addr, &input) {
+                    Some(Ok(out)) => {
+                        state.charge_gas(out.gas_cost);
+                        let n = out.output.len().min(ret_len);
+                        state.memory_write(ret_offset, &out.output[..n]);
+                        state.stack.push(U256::from_u64(1));
+                    }
+                    Some(Err(_)) | None => {
+                        // A failed precompile call or a target with no
+                        // bytecode this crate can execute both just fail the
+                        // call -- EVM `CALL` never traps the caller, it only
+                        // reports success/failure on the stack.
+                        state.stack.push(U256::ZERO);
+                    }
+                }
+            }
+            OP_LOG0..=OP_LOG4 => {
+                let num_topics = (op - OP_LOG0) as usize;
+                let offset = pop!().as_usize();
+                let len = pop!().as_usize();
+                let mut topics = Vec::with_capacity(num_topics);
+                for _ in 0..num_topics {
+                    topics.push(pop!());
+                }
+                let data = state.memory_read(offset, len);
+                system.emit_log(topics, data.into());
+            }
+            OP_SELFDESTRUCT => {
+                let addr = pop!();
+                let id = addr.low_u64();
+                return Ok(Output {
+                    status_code: StatusCode::Success,
+                    reverted: false,
+                    output_data: bytes::Bytes::new(),
+                    selfdestroyed: Some(Address::new_id(id)),
+                    gas_used: state.gas_used,
+                });
+            }
+            _ => trap!(),
+        }
+
+        pc += 1;
+    }
+}
@@ -0,0 +1,75 @@
+use bytes::Bytes;
+use fil_actors_runtime::ActorError;
+use fvm_shared::address::Address;
+
+use crate::interpreter::U256;
+
+/// Outcome of running a bytecode program to completion (or to a trap).
+#[derive(Debug)]
+pub enum StatusCode {
+    /// Execution finished normally.
+    Success,
+    /// Execution hit `REVERT`; `output_data` on the `Output` carries the
+    /// revert buffer.
+    Revert,
+    /// Something went wrong that should be surfaced to the calling actor
+    /// method as-is (e.g. a syscall failure already wrapped by the runtime).
+    ActorError(ActorError),
+}
+
+impl StatusCode {
+    /// Wrap a storage-layer failure (a `Kamt`/blockstore error surfaced as a
+    /// `String` by [`crate::interpreter::System`]) as the `ActorError`
+    /// variant, the same way every other hard failure from the interpreter
+    /// loop is surfaced.
+    pub fn interpreter_error(e: String) -> Self {
+        StatusCode::ActorError(ActorError::unspecified(e))
+    }
+}
+
+impl PartialEq for StatusCode {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (StatusCode::Success, StatusCode::Success) | (StatusCode::Revert, StatusCode::Revert)
+        )
+    }
+}
+
+impl std::fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusCode::Success => write!(f, "success"),
+            StatusCode::Revert => write!(f, "revert"),
+            StatusCode::ActorError(e) => write!(f, "actor error: {e}"),
+        }
+    }
+}
+
+/// A single EVM event emitted by `LOG0`-`LOG4`: up to four indexed topics
+/// plus an opaque data blob. Discarded if the frame that emitted it reverts.
+#[derive(Debug, Clone)]
+pub struct Log {
+    pub topics: Vec<U256>,
+    pub data: Bytes,
+}
+
+/// The result of executing a contract invocation (constructor or a regular
+/// call) from start to finish.
+#[derive(Debug)]
+pub struct Output {
+    pub status_code: StatusCode,
+    /// Set when execution terminated via `REVERT`. When set, `output_data`
+    /// holds the raw bytes the contract passed to `REVERT`, not the normal
+    /// return buffer.
+    pub reverted: bool,
+    /// Either the contract's normal return data, or -- when `reverted` is
+    /// set -- the raw `REVERT` buffer (which off-chain tooling can decode as
+    /// a Solidity `Error(string)`/`Panic(uint256)` ABI payload).
+    pub output_data: Bytes,
+    /// Set if the contract self-destructed during this invocation.
+    pub selfdestroyed: Option<Address>,
+    /// Gas the interpreter priced during this invocation. See
+    /// [`crate::interpreter::ExecutionState::gas_used`].
+    pub gas_used: u64,
+}
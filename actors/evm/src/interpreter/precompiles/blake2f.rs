@@ -0,0 +1,142 @@
+use crate::interpreter::StatusCode;
+
+use super::PrecompileOutput;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The BLAKE2b `F` compression function (RFC 7693 section 3.2), run `rounds`
+/// times. This is the primitive EIP-152 exposes directly, distinct from the
+/// full `sha256`-style precompiles which hash an arbitrary-length input.
+fn f(rounds: u32, h: &mut [u64; 8], m: [u64; 16], t: [u64; 2], final_block: bool) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if final_block {
+        v[14] = !v[14];
+    }
+
+    for round in 0..rounds as usize {
+        let s = &SIGMA[round % 10];
+        g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// `0x09`: the BLAKE2b compression function `F`, as specified by EIP-152.
+/// Input is `rounds (4 bytes big-endian) || h (8 * 8 bytes LE) ||
+/// m (16 * 8 bytes LE) || t (2 * 8 bytes LE) || final (1 byte)`; gas cost is
+/// exactly `rounds`.
+pub fn call(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    if input.len() != 213 {
+        return Err(StatusCode::Revert);
+    }
+    let final_byte = input[212];
+    if final_byte > 1 {
+        return Err(StatusCode::Revert);
+    }
+
+    let rounds = u32::from_be_bytes(input[0..4].try_into().unwrap());
+
+    let mut h = [0u64; 8];
+    for (i, chunk) in input[4..68].chunks_exact(8).enumerate() {
+        h[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut m = [0u64; 16];
+    for (i, chunk) in input[68..196].chunks_exact(8).enumerate() {
+        m[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let t = [
+        u64::from_le_bytes(input[196..204].try_into().unwrap()),
+        u64::from_le_bytes(input[204..212].try_into().unwrap()),
+    ];
+
+    f(rounds, &mut h, m, t, final_byte == 1);
+
+    let mut out = Vec::with_capacity(64);
+    for word in h {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+
+    Ok(PrecompileOutput::new(out, rounds as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_length_input_reverts() {
+        assert!(matches!(call(&[0u8; 10]), Err(StatusCode::Revert)));
+    }
+
+    #[test]
+    fn invalid_final_block_flag_reverts() {
+        let mut input = [0u8; 213];
+        input[212] = 2;
+        assert!(matches!(call(&input), Err(StatusCode::Revert)));
+    }
+
+    #[test]
+    fn gas_cost_equals_rounds() {
+        let mut input = [0u8; 213];
+        input[0..4].copy_from_slice(&7u32.to_be_bytes());
+        let output = call(&input).unwrap();
+        assert_eq!(output.gas_cost, 7);
+        assert_eq!(output.output.len(), 64);
+    }
+
+    #[test]
+    fn compression_is_deterministic() {
+        let mut input = [0u8; 213];
+        input[0..4].copy_from_slice(&12u32.to_be_bytes());
+        input[212] = 1;
+        assert_eq!(call(&input).unwrap().output, call(&input).unwrap().output);
+    }
+}
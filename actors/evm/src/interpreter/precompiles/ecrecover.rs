@@ -0,0 +1,66 @@
+use libsecp256k1::{recover, Message, RecoveryId, Signature};
+
+use crate::interpreter::StatusCode;
+
+use super::PrecompileOutput;
+
+const GAS_COST: u64 = 3_000;
+
+/// `0x01`: secp256k1 public-key recovery. Input is `hash || v || r || s`,
+/// each field a 32-byte word (`v` is 27 or 28). Output is the recovered
+/// address, left-padded to 32 bytes; malformed input or a signature that
+/// fails to recover returns an empty result rather than an error, matching
+/// Ethereum's behavior.
+pub fn call(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    let mut buf = [0u8; 128];
+    let len = input.len().min(128);
+    buf[..len].copy_from_slice(&input[..len]);
+
+    let hash = &buf[0..32];
+    let v = buf[63];
+    let sig_bytes = &buf[64..128];
+
+    let recovered = (|| {
+        if !(27..=28).contains(&v) || buf[32..63].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let recovery_id = RecoveryId::parse(v - 27).ok()?;
+        let message = Message::parse_slice(hash).ok()?;
+        let signature = Signature::parse_standard_slice(sig_bytes).ok()?;
+        let pubkey = recover(&message, &signature, &recovery_id).ok()?;
+
+        // Ethereum addresses are the low 20 bytes of keccak256(pubkey).
+        let uncompressed = pubkey.serialize();
+        let hash = crate::interpreter::precompiles::keccak256(&uncompressed[1..]);
+        let mut out = [0u8; 32];
+        out[12..].copy_from_slice(&hash[12..]);
+        Some(out)
+    })();
+
+    Ok(PrecompileOutput::new(recovered.map(|b| b.to_vec()).unwrap_or_default(), GAS_COST))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_recovery_id_returns_empty_output_not_an_error() {
+        // `v` must be 27 or 28; anything else is malformed input, which
+        // Ethereum (and this precompile) treats as "no recovery", not a
+        // failed call.
+        let mut input = [0u8; 128];
+        input[63] = 26;
+        let output = call(&input).unwrap();
+        assert!(output.output.is_empty());
+        assert_eq!(output.gas_cost, GAS_COST);
+    }
+
+    #[test]
+    fn all_zero_signature_fails_to_recover() {
+        let mut input = [0u8; 128];
+        input[63] = 27;
+        let output = call(&input).unwrap();
+        assert!(output.output.is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+use ripemd::{Digest, Ripemd160};
+use sha2::Sha256;
+
+use crate::interpreter::StatusCode;
+
+use super::PrecompileOutput;
+
+const GAS_BASE: u64 = 60;
+const GAS_PER_WORD: u64 = 12;
+
+const RIPEMD_GAS_BASE: u64 = 600;
+const RIPEMD_GAS_PER_WORD: u64 = 120;
+
+fn words(len: usize) -> u64 {
+    (len as u64 + 31) / 32
+}
+
+/// `0x02`: SHA-256.
+pub fn sha256(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    let digest = Sha256::digest(input);
+    let gas_cost = GAS_BASE + GAS_PER_WORD * words(input.len());
+    Ok(PrecompileOutput::new(digest.to_vec(), gas_cost))
+}
+
+/// `0x03`: RIPEMD-160, left-padded to a 32-byte word as the EVM ABI expects.
+pub fn ripemd160(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    let digest = Ripemd160::digest(input);
+    let mut padded = [0u8; 32];
+    padded[12..].copy_from_slice(&digest);
+    let gas_cost = RIPEMD_GAS_BASE + RIPEMD_GAS_PER_WORD * words(input.len());
+    Ok(PrecompileOutput::new(padded.to_vec(), gas_cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_of_empty_input() {
+        // Standard SHA-256 test vector.
+        let output = sha256(&[]).unwrap();
+        assert_eq!(
+            hex::encode(output.output),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn ripemd160_of_abc() {
+        // Standard RIPEMD-160 test vector: RIPEMD160("abc").
+        let output = ripemd160(b"abc").unwrap();
+        assert_eq!(
+            hex::encode(&output.output[12..]),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert!(output.output[..12].iter().all(|b| *b == 0));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::interpreter::StatusCode;
+
+use super::PrecompileOutput;
+
+const GAS_BASE: u64 = 15;
+const GAS_PER_WORD: u64 = 3;
+
+/// `0x04`: returns its input unchanged (a.k.a. `datacopy`).
+pub fn call(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    let words = (input.len() as u64 + 31) / 32;
+    let gas_cost = GAS_BASE + GAS_PER_WORD * words;
+    Ok(PrecompileOutput::new(input.to_vec(), gas_cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_input_unchanged() {
+        let output = call(b"hello world").unwrap();
+        assert_eq!(output.output, b"hello world");
+        assert_eq!(output.gas_cost, GAS_BASE + GAS_PER_WORD);
+    }
+}
@@ -0,0 +1,177 @@
+use num_bigint::BigUint;
+
+use crate::interpreter::StatusCode;
+
+use super::PrecompileOutput;
+
+const GAS_QUADRATIC_DIVISOR: u64 = 3;
+const MIN_GAS: u64 = 200;
+
+/// Upper bound on `base_len`/`exp_len`/`mod_len`. Each length is attacker
+/// controlled and otherwise flows straight into a `Vec` allocation (and, for
+/// `base_len` and `exp_len`, the `base_len + exp_len` offset addition); without a cap a
+/// single call with huge length words in the input can OOM or overflow
+/// `usize` well before `gas_cost` ever gets a chance to price the call out.
+/// 1 MiB is already far larger than any real modexp use needs.
+const MAX_LEN: usize = 1 << 20;
+
+fn read_u256_len(bytes: &[u8]) -> usize {
+    let mut buf = [0u8; 32];
+    let n = bytes.len().min(32);
+    buf[32 - n..].copy_from_slice(&bytes[..n]);
+    // Saturate rather than overflow `usize` on adversarial (huge) lengths.
+    // `to_u64_digits` is least-significant first, so more than one digit
+    // means the value is at least 2^64.
+    match BigUint::from_bytes_be(&buf).to_u64_digits().as_slice() {
+        [] => 0,
+        [v] => usize::try_from(*v).unwrap_or(usize::MAX),
+        _ => usize::MAX,
+    }
+}
+
+/// `0x05`: arbitrary-precision modular exponentiation, `base^exp % modulus`.
+/// Input is three big-endian length words (`base_len`, `exp_len`,
+/// `mod_len`) followed by the three values themselves, each `*_len` bytes.
+pub fn call(input: &[u8]) -> Result<PrecompileOutput, StatusCode> {
+    let get_word = |i: usize| -> &[u8] {
+        let start = i * 32;
+        if start >= input.len() {
+            &[]
+        } else {
+            &input[start..(start + 32).min(input.len())]
+        }
+    };
+
+    let base_len = read_u256_len(get_word(0));
+    let exp_len = read_u256_len(get_word(1));
+    let mod_len = read_u256_len(get_word(2));
+
+    if base_len > MAX_LEN || exp_len > MAX_LEN || mod_len > MAX_LEN {
+        return Err(StatusCode::Revert);
+    }
+
+    let data_start = 96;
+    let read_value = |offset: usize, len: usize| -> Vec<u8> {
+        let start = data_start + offset;
+        let mut buf = vec![0u8; len];
+        if start < input.len() {
+            let avail = (input.len() - start).min(len);
+            buf[..avail].copy_from_slice(&input[start..start + avail]);
+        }
+        buf
+    };
+
+    let base = BigUint::from_bytes_be(&read_value(0, base_len));
+    let exp_bytes = read_value(base_len, exp_len);
+    let exp = BigUint::from_bytes_be(&exp_bytes);
+    let modulus = BigUint::from_bytes_be(&read_value(base_len + exp_len, mod_len));
+
+    let gas_cost = gas_cost(base_len, exp_len, mod_len, &exp_bytes);
+
+    let result = if modulus == BigUint::from(0u8) {
+        BigUint::from(0u8)
+    } else {
+        base.modpow(&exp, &modulus)
+    };
+
+    if mod_len == 0 {
+        return Ok(PrecompileOutput::new(Vec::new(), gas_cost));
+    }
+
+    let mut out = vec![0u8; mod_len];
+    let result_bytes = result.to_bytes_be();
+    // `result_bytes` can be longer than `mod_len` only when `result` is zero
+    // (`to_bytes_be()` returns `[0]` rather than an empty slice), in which
+    // case the extra leading zero byte is simply dropped.
+    let result_bytes = &result_bytes[result_bytes.len().saturating_sub(mod_len)..];
+    let start = mod_len - result_bytes.len();
+    out[start..].copy_from_slice(result_bytes);
+
+    Ok(PrecompileOutput::new(out, gas_cost))
+}
+
+/// EIP-2565 gas schedule: `max(200, complexity(base_len, mod_len) *
+/// max(exp_bit_length, 1) / 3)`.
+fn gas_cost(base_len: usize, _exp_len: usize, mod_len: usize, exp: &[u8]) -> u64 {
+    let max_len = base_len.max(mod_len) as u64;
+    let words = (max_len + 7) / 8;
+    let complexity = words * words;
+
+    let exp_bit_len = {
+        let leading_zero_bytes = exp.iter().take_while(|b| **b == 0).count();
+        let significant = &exp[leading_zero_bytes..];
+        if significant.is_empty() {
+            0
+        } else {
+            (significant.len() - 1) * 8 + (8 - significant[0].leading_zeros() as usize)
+        }
+    };
+
+    let iteration_count = exp_bit_len.max(1) as u64;
+    (complexity * iteration_count / GAS_QUADRATIC_DIVISOR).max(MIN_GAS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn len_word(len: usize) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(len as u64).to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn three_squared_mod_five_is_four() {
+        // base_len = exp_len = mod_len = 1, base = 3, exp = 2, modulus = 5.
+        let mut input = Vec::new();
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&[3, 2, 5]);
+
+        let output = call(&input).unwrap();
+        assert_eq!(output.output, vec![4]);
+    }
+
+    #[test]
+    fn zero_modulus_returns_zero() {
+        let mut input = Vec::new();
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&[3, 2, 0]);
+
+        let output = call(&input).unwrap();
+        assert_eq!(output.output, vec![0]);
+    }
+
+    #[test]
+    fn zero_length_modulus_returns_empty_output() {
+        // A zero-length modulus is valid EVM input (e.g. `dispatches_known_addresses`
+        // calls `modexp::call(&[])`, which yields all-zero lengths) and must return
+        // empty output rather than panicking when writing the result back.
+        let mut input = Vec::new();
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(0));
+        input.extend_from_slice(&[3, 2]);
+
+        let output = call(&input).unwrap();
+        assert_eq!(output.output, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn oversized_length_word_reverts_instead_of_allocating() {
+        // An attacker-controlled length word claiming far more than MAX_LEN
+        // bytes must be rejected before any length-sized allocation happens,
+        // rather than trying to honor it (and OOMing, or overflowing the
+        // `base_len + exp_len` addition further down).
+        let mut input = Vec::new();
+        input.extend_from_slice(&len_word(MAX_LEN + 1));
+        input.extend_from_slice(&len_word(1));
+        input.extend_from_slice(&len_word(1));
+
+        assert!(matches!(call(&input), Err(StatusCode::Revert)));
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::{HashMap, HashSet};
+
+use cid::Cid;
+use fil_actors_runtime::{runtime::Runtime, ActorError};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_kamt::Kamt;
+use fvm_shared::address::Address;
+
+use bytes::Bytes;
+
+use crate::interpreter::block::{cid_to_u256, BlockContext, CHAIN_ID};
+use crate::interpreter::{Log, U256};
+
+/// Gas cost of a cold `SLOAD`/`SSTORE`/account access per EIP-2929.
+pub const COLD_ACCESS_COST: u64 = 2100;
+/// Gas cost of a warm `SLOAD`/`SSTORE`/account access per EIP-2929.
+pub const WARM_ACCESS_COST: u64 = 100;
+
+/// The EIP-2929 access cost for a slot/address that was (or wasn't) already
+/// warm. Pulled out as a pure function so the interpreter's `was this cold?`
+/// -> `what does it cost?` mapping can be tested without going through a
+/// full opcode dispatch.
+pub fn access_cost(cold: bool) -> u64 {
+    if cold { COLD_ACCESS_COST } else { WARM_ACCESS_COST }
+}
+
+/// A point in the mutation history that [`Storage::rollback`] can restore to.
+/// Opaque to callers; the only valid uses are capturing one from
+/// [`Storage::snapshot`] and later handing it back to `rollback`.
+#[derive(Clone, Copy)]
+pub struct SnapshotId {
+    storage_journal_len: usize,
+    transient_journal_len: usize,
+    warm_slot_journal_len: usize,
+    warm_address_journal_len: usize,
+    logs_len: usize,
+}
+
+/// Owned storage state for one top-level invocation: the in-memory overlay
+/// over the loaded `Kamt`, its mutation journal, and transient (EIP-1153)
+/// storage with a journal of its own. Lives alongside the `Kamt` in the
+/// actor method and is handed to `System` by mutable reference, the same
+/// way the `Kamt` is, so it survives across `System::reborrow` calls.
+///
+/// The `Kamt` itself is threaded through as an explicit parameter on the
+/// methods that need it (`load`/`get_storage`/`set_storage`/`flush_state`)
+/// rather than being held by `Storage`, so this type -- and everything it
+/// does with the overlay, journals, and logs -- can be exercised in tests
+/// against a plain `MemoryBlockstore`-backed `Kamt`, without needing a
+/// `Runtime` implementation.
+#[derive(Default)]
+pub struct Storage {
+    overlay: HashMap<U256, U256>,
+    /// `(key, previous_value, was_dirty_before)` per overlay write, so
+    /// `rollback` can restore both the value and whether the slot was
+    /// already pending writeback before this frame touched it.
+    storage_journal: Vec<(U256, U256, bool)>,
+    /// Slots written into `overlay` since the last `flush_state`; only these
+    /// are written back to the `Kamt`, so repeated reads of an unchanged
+    /// slot within a transaction don't cost a write.
+    dirty: HashSet<U256>,
+
+    transient: HashMap<U256, U256>,
+    transient_journal: Vec<(U256, U256)>,
+
+    /// EIP-2929 access set: storage slots and addresses already touched in
+    /// this transaction, and therefore chargeable at the warm gas cost.
+    warm_slots: HashSet<U256>,
+    /// Slots newly marked warm since this `Storage` was created (`flush_state`
+    /// does not clear it), in the order
+    /// they were first touched, so `rollback` can un-warm exactly the ones
+    /// a reverted frame introduced.
+    warm_slot_journal: Vec<U256>,
+    warm_addresses: HashSet<Address>,
+    /// Addresses newly marked warm since this `Storage` was created, in the
+    /// order they were first touched, mirroring `warm_slot_journal` so
+    /// `rollback` can un-warm exactly the ones a reverted frame introduced.
+    warm_address_journal: Vec<Address>,
+
+    /// Events emitted by `LOG0`-`LOG4` this invocation, in order. Append-only
+    /// like the journals above, so a revert can simply truncate it back to
+    /// the length recorded in its snapshot.
+    logs: Vec<Log>,
+}
+
+impl Storage {
+    /// Load a slot's current value, pulling it from the backing `Kamt` into
+    /// the overlay (and marking it warm) on first access.
+    fn load<BS: Blockstore>(
+        &mut self,
+        kamt: &mut Kamt<BS, U256, U256>,
+        key: U256,
+    ) -> Result<U256, String> {
+        self.mark_warm(key);
+        if let Some(v) = self.overlay.get(&key) {
+            return Ok(*v);
+        }
+        let v = kamt.get(&key).map_err(|e| e.to_string())?.copied().unwrap_or(U256::ZERO);
+        self.overlay.insert(key, v);
+        Ok(v)
+    }
+
+    pub fn get_storage<BS: Blockstore>(
+        &mut self,
+        kamt: &mut Kamt<BS, U256, U256>,
+        key: U256,
+    ) -> Result<Option<U256>, String> {
+        let v = self.load(kamt, key)?;
+        Ok(if v.is_zero() { None } else { Some(v) })
+    }
+
+    pub fn set_storage<BS: Blockstore>(
+        &mut self,
+        kamt: &mut Kamt<BS, U256, U256>,
+        key: U256,
+        value: U256,
+    ) -> Result<(), String> {
+        let prev = self.load(kamt, key)?;
+        if prev != value {
+            let was_dirty = self.dirty.contains(&key);
+            self.storage_journal.push((key, prev, was_dirty));
+            self.overlay.insert(key, value);
+            self.dirty.insert(key);
+        }
+        Ok(())
+    }
+
+    /// Whether `slot` has already been accessed this transaction (and is
+    /// therefore chargeable at [`WARM_ACCESS_COST`] instead of
+    /// [`COLD_ACCESS_COST`]).
+    pub fn is_warm(&self, slot: U256) -> bool {
+        self.warm_slots.contains(&slot)
+    }
+
+    /// Mark `slot` as accessed. Returns `true` if it was cold (this is its
+    /// first access this transaction), which is what the interpreter needs
+    /// to know to charge the right gas.
+    pub fn mark_warm(&mut self, slot: U256) -> bool {
+        let cold = self.warm_slots.insert(slot);
+        if cold {
+            self.warm_slot_journal.push(slot);
+        }
+        cold
+    }
+
+    pub fn is_warm_address(&self, addr: &Address) -> bool {
+        self.warm_addresses.contains(addr)
+    }
+
+    pub fn mark_warm_address(&mut self, addr: Address) -> bool {
+        let cold = self.warm_addresses.insert(addr);
+        if cold {
+            self.warm_address_journal.push(addr);
+        }
+        cold
+    }
+
+    pub fn get_transient_storage(&self, key: U256) -> U256 {
+        self.transient.get(&key).copied().unwrap_or(U256::ZERO)
+    }
+
+    pub fn set_transient_storage(&mut self, key: U256, value: U256) {
+        let prev = self.get_transient_storage(key);
+        if prev != value {
+            self.transient_journal.push((key, prev));
+            self.transient.insert(key, value);
+        }
+    }
+
+    /// Record the current position in the mutation journals so a later
+    /// `rollback` can undo everything a call frame does from this point on.
+    pub fn snapshot(&self) -> SnapshotId {
+        SnapshotId {
+            storage_journal_len: self.storage_journal.len(),
+            transient_journal_len: self.transient_journal.len(),
+            warm_slot_journal_len: self.warm_slot_journal.len(),
+            warm_address_journal_len: self.warm_address_journal.len(),
+            logs_len: self.logs.len(),
+        }
+    }
+
+    /// Undo every storage write, transient-storage write, slot/address
+    /// warming, and emitted log made since `snapshot` was taken.
+    pub fn rollback(&mut self, snapshot: SnapshotId) {
+        while self.storage_journal.len() > snapshot.storage_journal_len {
+            let (key, prev, was_dirty) = self.storage_journal.pop().unwrap();
+            self.overlay.insert(key, prev);
+            if was_dirty {
+                self.dirty.insert(key);
+            } else {
+                self.dirty.remove(&key);
+            }
+        }
+        while self.transient_journal.len() > snapshot.transient_journal_len {
+            let (key, prev) = self.transient_journal.pop().unwrap();
+            self.transient.insert(key, prev);
+        }
+        while self.warm_slot_journal.len() > snapshot.warm_slot_journal_len {
+            let slot = self.warm_slot_journal.pop().unwrap();
+            self.warm_slots.remove(&slot);
+        }
+        while self.warm_address_journal.len() > snapshot.warm_address_journal_len {
+            let addr = self.warm_address_journal.pop().unwrap();
+            self.warm_addresses.remove(&addr);
+        }
+        self.logs.truncate(snapshot.logs_len);
+    }
+
+    /// Append a `LOG0`-`LOG4` event. `topics` must have between 0 and 4
+    /// entries; the interpreter's opcode dispatch enforces that before
+    /// calling in.
+    pub fn emit_log(&mut self, topics: Vec<U256>, data: Bytes) {
+        self.logs.push(Log { topics, data });
+    }
+
+    /// Drain the logs emitted so far, in emission order. Called once
+    /// execution completes successfully, to hand them to the FVM's
+    /// actor-event mechanism.
+    pub fn take_logs(&mut self) -> Vec<Log> {
+        std::mem::take(&mut self.logs)
+    }
+
+    /// Write changed slots from the overlay into the storage `Kamt` and
+    /// return its root CID. Only slots in `dirty` are written back -- a
+    /// contract that reads the same hot slots all transaction without
+    /// writing them costs one `Kamt` round-trip, not one per access.
+    /// Transient storage is never persisted: it is dropped along with
+    /// `Storage` at the end of the invocation.
+    pub fn flush_state<BS: Blockstore>(
+        &mut self,
+        kamt: &mut Kamt<BS, U256, U256>,
+    ) -> Result<Cid, String> {
+        let dirty: Vec<U256> = self.dirty.drain().collect();
+        for key in dirty {
+            let value = self.overlay.get(&key).copied().unwrap_or(U256::ZERO);
+            let result = if value.is_zero() {
+                kamt.delete(&key).map(|_| ())
+            } else {
+                kamt.set(key, value).map(|_| ())
+            };
+            result.map_err(|e| format!("failed to update storage slot: {e:?}"))?;
+        }
+        kamt.flush().map_err(|e| format!("failed to flush storage: {e:?}"))
+    }
+}
+
+/// The platform abstraction layer the interpreter executes against: storage
+/// (persistent and transient), the EIP-2929 access sets, emitted logs, and
+/// block/chain context (via [`BlockContext`]).
+///
+/// Storage writes land in the in-memory `overlay` rather than the `Kamt`
+/// directly, with every overwrite recorded in an append-only journal of
+/// `(key, previous_value, was_dirty)` entries. This gives call frames standard EVM
+/// rollback semantics: a reverted sub-call can undo exactly the writes it
+/// made (and nothing else) by replaying the journal backwards to a snapshot
+/// taken before it started. Transient storage (`TLOAD`/`TSTORE`) is tracked
+/// the same way but lives only for the duration of the top-level invocation
+/// and is never written to the Kamt.
+pub struct System<'r, BS, RT> {
+    pub(crate) rt: &'r mut RT,
+    kamt: &'r mut Kamt<BS, U256, U256>,
+    storage: &'r mut Storage,
+}
+
+impl<'r, BS, RT> System<'r, BS, RT>
+where
+    BS: Blockstore + Clone,
+    RT: Runtime<BS>,
+{
+    pub fn new(
+        rt: &'r mut RT,
+        kamt: &'r mut Kamt<BS, U256, U256>,
+        storage: &'r mut Storage,
+    ) -> anyhow::Result<Self> {
+        // Per EIP-2929, a contract's own address is warm from the start of
+        // the transaction.
+        storage.warm_addresses.insert(rt.message().receiver());
+        Ok(System { rt, kamt, storage })
+    }
+
+    /// Re-borrow this `System` for the duration of a single `execute` call,
+    /// so the original owner can still be used afterwards (to flush state,
+    /// inspect self-destructs, etc).
+    pub fn reborrow(&mut self) -> System<'_, BS, RT> {
+        System { rt: self.rt, kamt: self.kamt, storage: self.storage }
+    }
+
+    pub fn get_storage(&mut self, key: U256) -> Result<Option<U256>, String> {
+        self.storage.get_storage(self.kamt, key)
+    }
+
+    pub fn set_storage(&mut self, key: U256, value: U256) -> Result<(), String> {
+        self.storage.set_storage(self.kamt, key, value)
+    }
+
+    pub fn is_warm(&self, slot: U256) -> bool {
+        self.storage.is_warm(slot)
+    }
+
+    pub fn mark_warm(&mut self, slot: U256) -> bool {
+        self.storage.mark_warm(slot)
+    }
+
+    pub fn is_warm_address(&self, addr: &Address) -> bool {
+        self.storage.is_warm_address(addr)
+    }
+
+    pub fn mark_warm_address(&mut self, addr: Address) -> bool {
+        self.storage.mark_warm_address(addr)
+    }
+
+    pub fn get_transient_storage(&self, key: U256) -> U256 {
+        self.storage.get_transient_storage(key)
+    }
+
+    pub fn set_transient_storage(&mut self, key: U256, value: U256) {
+        self.storage.set_transient_storage(key, value)
+    }
+
+    pub fn snapshot(&self) -> SnapshotId {
+        self.storage.snapshot()
+    }
+
+    pub fn rollback(&mut self, snapshot: SnapshotId) {
+        self.storage.rollback(snapshot)
+    }
+
+    /// Append a `LOG0`-`LOG4` event. `topics` must have between 0 and 4
+    /// entries; the interpreter's opcode dispatch enforces that before
+    /// calling in.
+    pub fn emit_log(&mut self, topics: Vec<U256>, data: Bytes) {
+        self.storage.emit_log(topics, data)
+    }
+
+    /// Drain the logs emitted so far, in emission order. Called once
+    /// execution completes successfully, to hand them to the FVM's
+    /// actor-event mechanism.
+    pub fn take_logs(&mut self) -> Vec<Log> {
+        self.storage.take_logs()
+    }
+
+    pub fn flush_state(&mut self) -> Result<Cid, ActorError> {
+        self.storage
+            .flush_state(self.kamt)
+            .map_err(ActorError::illegal_state)
+    }
+}
+
+impl<'r, BS, RT> BlockContext for System<'r, BS, RT>
+where
+    BS: Blockstore + Clone,
+    RT: Runtime<BS>,
+{
+    fn block_height(&self) -> u64 {
+        self.rt.curr_epoch() as u64
+    }
+
+    fn timestamp(&self, height: u64) -> u64 {
+        if height == self.block_height() {
+            self.rt.tipset_timestamp()
+        } else {
+            // The FVM only exposes the current tipset's timestamp; anything
+            // else reads as zero, same as an out-of-window `BLOCKHASH`.
+            0
+        }
+    }
+
+    fn block_hash(&self, height: u64) -> U256 {
+        let current = self.block_height();
+        // BLOCKHASH only covers the 256 most recent, already-produced blocks.
+        if height >= current || current - height > 256 {
+            return U256::ZERO;
+        }
+        self.rt.tipset_cid(height).map(|cid| cid_to_u256(&cid)).unwrap_or(U256::ZERO)
+    }
+
+    fn coinbase(&self) -> Address {
+        Address::new_id(0)
+    }
+
+    fn chain_id(&self) -> U256 {
+        U256::from_u64(CHAIN_ID)
+    }
+
+    fn base_fee(&self) -> U256 {
+        U256::from_big_endian(&self.rt.base_fee().atto().to_signed_bytes_be())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_kamt::Kamt;
+
+    use super::*;
+
+    fn new_kamt() -> Kamt<MemoryBlockstore, U256, U256> {
+        Kamt::new(MemoryBlockstore::new())
+    }
+
+    #[test]
+    fn set_then_get_storage_round_trips() {
+        let mut kamt = new_kamt();
+        let mut storage = Storage::default();
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(42)).unwrap();
+        assert_eq!(storage.get_storage(&mut kamt, U256::from_u64(1)).unwrap(), Some(U256::from_u64(42)));
+    }
+
+    #[test]
+    fn rollback_undoes_writes_after_snapshot() {
+        let mut kamt = new_kamt();
+        let mut storage = Storage::default();
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(10)).unwrap();
+
+        let snapshot = storage.snapshot();
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(20)).unwrap();
+        storage.set_storage(&mut kamt, U256::from_u64(2), U256::from_u64(99)).unwrap();
+
+        storage.rollback(snapshot);
+
+        assert_eq!(storage.get_storage(&mut kamt, U256::from_u64(1)).unwrap(), Some(U256::from_u64(10)));
+        assert_eq!(storage.get_storage(&mut kamt, U256::from_u64(2)).unwrap(), None);
+    }
+
+    #[test]
+    fn rollback_restores_dirty_flag() {
+        let mut kamt = new_kamt();
+        let mut storage = Storage::default();
+        // Slot 2 is untouched before the snapshot; slot 1 is already dirty.
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(10)).unwrap();
+
+        let snapshot = storage.snapshot();
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(20)).unwrap();
+        storage.set_storage(&mut kamt, U256::from_u64(2), U256::from_u64(99)).unwrap();
+        assert!(storage.dirty.contains(&U256::from_u64(1)));
+        assert!(storage.dirty.contains(&U256::from_u64(2)));
+
+        storage.rollback(snapshot);
+
+        // Slot 1 was dirty before the snapshot and stays dirty; slot 2's
+        // dirty mark was introduced after the snapshot and must be undone.
+        assert!(storage.dirty.contains(&U256::from_u64(1)));
+        assert!(!storage.dirty.contains(&U256::from_u64(2)));
+    }
+
+    #[test]
+    fn rollback_restores_warm_slots() {
+        let mut storage = Storage::default();
+        storage.mark_warm(U256::from_u64(1));
+
+        let snapshot = storage.snapshot();
+        storage.mark_warm(U256::from_u64(2));
+        assert!(storage.is_warm(U256::from_u64(2)));
+
+        storage.rollback(snapshot);
+
+        assert!(storage.is_warm(U256::from_u64(1)));
+        assert!(!storage.is_warm(U256::from_u64(2)));
+    }
+
+    #[test]
+    fn rollback_restores_warm_addresses() {
+        let mut storage = Storage::default();
+        storage.mark_warm_address(Address::new_id(1));
+
+        let snapshot = storage.snapshot();
+        storage.mark_warm_address(Address::new_id(2));
+        assert!(storage.is_warm_address(&Address::new_id(2)));
+
+        storage.rollback(snapshot);
+
+        assert!(storage.is_warm_address(&Address::new_id(1)));
+        assert!(!storage.is_warm_address(&Address::new_id(2)));
+    }
+
+    #[test]
+    fn rollback_undoes_transient_storage() {
+        let mut kamt = new_kamt();
+        let mut storage = Storage::default();
+        storage.set_transient_storage(U256::from_u64(1), U256::from_u64(10));
+
+        let snapshot = storage.snapshot();
+        storage.set_transient_storage(U256::from_u64(1), U256::from_u64(20));
+        storage.rollback(snapshot);
+
+        assert_eq!(storage.get_transient_storage(U256::from_u64(1)), U256::from_u64(10));
+    }
+
+    #[test]
+    fn flush_state_only_writes_dirty_slots() {
+        let mut kamt = new_kamt();
+        let mut storage = Storage::default();
+        storage.set_storage(&mut kamt, U256::from_u64(1), U256::from_u64(7)).unwrap();
+        storage.flush_state(&mut kamt).unwrap();
+
+        // Reading it back afterwards shouldn't mark it dirty again.
+        storage.get_storage(&mut kamt, U256::from_u64(1)).unwrap();
+        assert!(!storage.dirty.contains(&U256::from_u64(1)));
+        let root_before = storage.flush_state(&mut kamt).unwrap();
+
+        // A fresh `Storage` has an empty overlay, so this read comes from the
+        // reloaded Kamt rather than from `storage`'s cached value.
+        let mut kamt2 = Kamt::load(&root_before, kamt.store().clone()).unwrap();
+        assert_eq!(
+            Storage::default().get_storage(&mut kamt2, U256::from_u64(1)).unwrap(),
+            Some(U256::from_u64(7))
+        );
+    }
+
+    #[test]
+    fn mark_warm_reports_cold_once() {
+        let mut storage = Storage::default();
+        assert!(storage.mark_warm(U256::from_u64(1)));
+        assert!(!storage.mark_warm(U256::from_u64(1)));
+        assert!(storage.is_warm(U256::from_u64(1)));
+    }
+
+    #[test]
+    fn access_cost_matches_cold_then_warm_sequence() {
+        let mut storage = Storage::default();
+        let slot = U256::from_u64(1);
+
+        let first_access_cold = storage.mark_warm(slot);
+        assert_eq!(access_cost(first_access_cold), COLD_ACCESS_COST);
+
+        let second_access_cold = storage.mark_warm(slot);
+        assert_eq!(access_cost(second_access_cold), WARM_ACCESS_COST);
+    }
+}
@@ -0,0 +1,79 @@
+//! A 256-bit unsigned integer, the native word size of the EVM.
+//!
+//! This is a thin newtype so the rest of the interpreter isn't tied to a
+//! particular big-integer crate.
+
+use std::fmt;
+
+/// A 256-bit unsigned integer stored big-endian as four `u64` limbs
+/// (most-significant limb first).
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+
+    pub fn from_u64(v: u64) -> Self {
+        U256([0, 0, 0, v])
+    }
+
+    /// Interpret `bytes` as a big-endian 32-byte word, left-padding with
+    /// zeroes if shorter.
+    pub fn from_big_endian(bytes: &[u8]) -> Self {
+        let mut buf = [0u8; 32];
+        let start = 32usize.saturating_sub(bytes.len());
+        buf[start..].copy_from_slice(&bytes[bytes.len().saturating_sub(32)..]);
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in buf.chunks_exact(8).enumerate() {
+            limbs[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            out[i * 8..i * 8 + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        out
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// The low 64 bits, discarding anything above them.
+    pub fn low_u64(&self) -> u64 {
+        self.0[3]
+    }
+
+    /// The value as a `usize`, for use as a memory offset/length or jump
+    /// target. A value wider than `usize` saturates to `usize::MAX` rather
+    /// than wrapping, the same way real EVM implementations treat
+    /// offsets/lengths wider than practically addressable memory as simply
+    /// enormous rather than as a distinct error case.
+    pub fn as_usize(&self) -> usize {
+        if self.0[..3].iter().any(|limb| *limb != 0) {
+            return usize::MAX;
+        }
+        usize::try_from(self.0[3]).unwrap_or(usize::MAX)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(v: u64) -> Self {
+        U256::from_u64(v)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
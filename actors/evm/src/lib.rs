@@ -1,8 +1,10 @@
 pub mod interpreter;
+pub mod registry;
 mod state;
 
 use {
-    crate::interpreter::{execute, Bytecode, ExecutionState, StatusCode, System, U256},
+    crate::interpreter::{execute, Bytecode, ExecutionState, Log, StatusCode, Storage, System, U256},
+    crate::registry::{RefParams, BYTECODE_REGISTRY_ACTOR_ID},
     crate::state::State,
     bytes::Bytes,
     cid::Cid,
@@ -15,7 +17,10 @@ use {
     fvm_ipld_encoding::tuple::*,
     fvm_ipld_encoding::RawBytes,
     fvm_ipld_kamt::{Config as KamtConfig, Kamt},
+    fvm_shared::address::Address,
+    fvm_shared::econ::TokenAmount,
     fvm_shared::error::*,
+    fvm_shared::event::{ActorEvent, Entry, Flags},
     fvm_shared::{MethodNum, METHOD_CONSTRUCTOR},
     num_derive::FromPrimitive,
     num_traits::FromPrimitive,
@@ -30,6 +35,136 @@ const MAX_CODE_SIZE: usize = 24 << 10;
 
 pub const EVM_CONTRACT_REVERTED: ExitCode = ExitCode::new(27);
 
+/// The outcome of a reverted invocation: the distinguished exit code plus
+/// the raw EVM revert buffer, so off-chain tooling can decode it as a
+/// Solidity `Error(string)`/`Panic(uint256)` ABI payload (selectors
+/// `0x08c379a0`/`0x4e487b71`) instead of seeing an opaque failure.
+struct RevertOutput {
+    exit_code: ExitCode,
+    revert_data: RawBytes,
+}
+
+impl RevertOutput {
+    fn new(output_data: Bytes) -> Self {
+        RevertOutput { exit_code: EVM_CONTRACT_REVERTED, revert_data: RawBytes::from(output_data.to_vec()) }
+    }
+
+    /// `ActorError`, as exposed by the `fil_actors_runtime` version this
+    /// crate builds against, only carries an exit code and a string message
+    /// -- there is no separate binary-payload channel -- so the revert
+    /// buffer is hex encoded into the message, where off-chain tooling can
+    /// pull it back out and decode it as a Solidity `Error(string)`/
+    /// `Panic(uint256)` ABI payload. This goes through the same
+    /// `ActorError` path every other failure in this file uses, rather than
+    /// a separate control-flow mechanism.
+    ///
+    /// This is a stopgap, not the preferred shape: hex-in-a-message-string
+    /// forces every caller to string-parse an error to get structured data
+    /// back out, and the message is not a stable contract the way a typed
+    /// payload would be. Once there's a binary-payload channel to put
+    /// `revert_data` on instead (either a future `ActorError` field, or
+    /// returning the revert buffer out-of-band the way a successful
+    /// invocation's receipt does in [`InvokeContractReturn`]), this should
+    /// move there and drop the hex encoding.
+    fn into_actor_error(self) -> ActorError {
+        ActorError::unchecked(self.exit_code, hex::encode(self.revert_data.to_vec()))
+    }
+}
+
+/// A `LOG0`-`LOG4` event as it appears in [`InvokeContractReturn`]: each
+/// topic as its raw 32-byte big-endian word, so the receipt doesn't tie its
+/// wire encoding to `U256`'s in-memory representation (which has no
+/// `Serialize`/`Deserialize` of its own).
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ReceiptLog {
+    pub topics: Vec<[u8; 32]>,
+    pub data: RawBytes,
+}
+
+impl From<&Log> for ReceiptLog {
+    fn from(log: &Log) -> Self {
+        ReceiptLog {
+            topics: log.topics.iter().map(|t| t.to_bytes()).collect(),
+            data: RawBytes::from(log.data.to_vec()),
+        }
+    }
+}
+
+/// Return value of a successful [`EvmContractActor::invoke_contract`]: the
+/// contract's normal return data plus every `LOG0`-`LOG4` event it emitted,
+/// in emission order. The logs are also emitted as FVM actor events (see
+/// [`log_to_actor_event`]) for indexers, but a direct caller -- another
+/// actor invoking this one by `MethodNum`, or off-chain tooling that wants
+/// the logs without re-deriving them from the event stream -- gets them
+/// back here too.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct InvokeContractReturn {
+    pub output_data: RawBytes,
+    pub logs: Vec<ReceiptLog>,
+}
+
+/// Translate a `LOG0`-`LOG4` event into the FVM's actor-event shape: one
+/// indexed entry per topic (`t0`..`t3`), plus the raw log data under `d`.
+fn log_to_actor_event(log: &Log) -> ActorEvent {
+    let mut entries: Vec<Entry> = log
+        .topics
+        .iter()
+        .enumerate()
+        .map(|(i, topic)| Entry {
+            flags: Flags::FLAG_INDEXED_VALUE,
+            key: format!("t{i}"),
+            codec: fvm_ipld_encoding::IPLD_RAW,
+            value: topic.to_bytes().to_vec(),
+        })
+        .collect();
+    entries.push(Entry {
+        flags: Flags::FLAG_INDEXED_VALUE,
+        key: "d".to_string(),
+        codec: fvm_ipld_encoding::IPLD_RAW,
+        value: log.data.to_vec(),
+    });
+    ActorEvent::from(entries)
+}
+
+/// Best-effort notification to the shared
+/// [`registry::BytecodeRegistryActor`] that a blob's reference count
+/// changed. Deploying and destroying EVM contracts must keep working
+/// whether or not that singleton has actually been instantiated at
+/// [`BYTECODE_REGISTRY_ACTOR_ID`] -- nothing in this crate wires it into
+/// genesis -- so a missing registry (or any other send failure) is swallowed
+/// here rather than surfaced: refcounting is bookkeeping for blob
+/// deduplication, not something a contract's own deploy/self-destruct should
+/// ever fail over.
+fn notify_blob_registry<BS, RT>(rt: &mut RT, method: MethodNum, blob: Cid)
+where
+    BS: Blockstore + Clone,
+    RT: Runtime<BS>,
+{
+    let Ok(params) = RawBytes::serialize(RefParams { blob }) else { return };
+    let _ = rt.send_simple(&Address::new_id(BYTECODE_REGISTRY_ACTOR_ID), method, params, TokenAmount::zero());
+}
+
+/// Tell the shared [`registry::BytecodeRegistryActor`] that one more contract
+/// now references `blob`. Called once per successful deploy, whether init
+/// code ran or `deploy_as_blob_cid` pointed straight at existing code.
+fn increment_blob_ref<BS, RT>(rt: &mut RT, blob: Cid)
+where
+    BS: Blockstore + Clone,
+    RT: Runtime<BS>,
+{
+    notify_blob_registry(rt, registry::Method::IncrementRef as MethodNum, blob)
+}
+
+/// Tell the shared [`registry::BytecodeRegistryActor`] that this contract no
+/// longer references `blob`, reclaiming the blob once it was the last one.
+fn decrement_blob_ref<BS, RT>(rt: &mut RT, blob: Cid)
+where
+    BS: Blockstore + Clone,
+    RT: Runtime<BS>,
+{
+    notify_blob_registry(rt, registry::Method::DecrementRef as MethodNum, blob)
+}
+
 lazy_static::lazy_static! {
     static ref KAMT_CONFIG: KamtConfig = KamtConfig {
         // The Solidity compiler creates contiguous array item keys.
@@ -55,6 +190,8 @@ pub enum Method {
     InvokeContract = 2,
     GetBytecode = 3,
     GetStorageAt = 4,
+    UploadBytecode = 5,
+    GetBytecodeBlob = 6,
 }
 
 pub struct EvmContractActor;
@@ -77,11 +214,37 @@ impl EvmContractActor {
             return Err(ActorError::illegal_argument("no bytecode provided".into()));
         }
 
+        if params.deploy_as_blob_cid {
+            // Deploy-by-reference: `bytecode` is the CID of runtime code
+            // already stored via `UploadBytecode`, so skip executing init
+            // code entirely and point the new contract straight at it.
+            let blob_cid = Cid::try_from(params.bytecode.to_vec())
+                .map_err(|e| ActorError::illegal_argument(format!("invalid blob cid: {e}")))?;
+            rt.store()
+                .get(&blob_cid)
+                .map_err(|e| ActorError::unspecified(format!("failed to load bytecode blob: {e:?}")))?
+                .ok_or_else(|| ActorError::not_found("referenced bytecode blob not found".to_string()))?;
+
+            let contract_state_cid = Kamt::<_, U256, U256>::new_with_config(
+                rt.store().clone(),
+                KAMT_CONFIG.to_owned(),
+            )
+            .flush()
+            .map_err(|e| ActorError::illegal_state(format!("failed to flush storage: {e:?}")))?;
+
+            let state = State { bytecode: blob_cid, contract_state: contract_state_cid };
+            rt.create(&state)?;
+            increment_blob_ref(rt, blob_cid);
+            return Ok(());
+        }
+
         // create an empty storage KAMT to pass it down for execution.
         let mut kamt = Kamt::new_with_config(rt.store().clone(), KAMT_CONFIG.to_owned());
+        // the overlay/journal the System rolls writes back through on revert.
+        let mut storage = Storage::default();
 
         // create an instance of the platform abstraction layer -- note: do we even need this?
-        let mut system = System::new(rt, &mut kamt).map_err(|e| {
+        let mut system = System::new(rt, &mut kamt, &mut storage).map_err(|e| {
             ActorError::unspecified(format!("failed to create execution abstraction layer: {e:?}"))
         })?;
 
@@ -92,6 +255,8 @@ impl EvmContractActor {
         let bytecode = Bytecode::new(&params.bytecode)
             .map_err(|e| ActorError::unspecified(format!("failed to parse bytecode: {e:?}")))?;
 
+        let snapshot = system.snapshot();
+
         // invoke the contract constructor
         let exec_status =
             execute(&bytecode, &mut exec_state, &mut system.reborrow()).map_err(|e| match e {
@@ -99,9 +264,13 @@ impl EvmContractActor {
                 _ => ActorError::unspecified(format!("EVM execution error: {e:?}")),
             })?;
 
-        // TODO this does not return revert data yet, but it has correct semantics.
         if exec_status.reverted {
-            Err(ActorError::unchecked(EVM_CONTRACT_REVERTED, "constructor reverted".to_string()))
+            system.rollback(snapshot);
+            // Carry the EVM revert buffer back to the caller instead of
+            // discarding it; exit code 27 still distinguishes a revert from
+            // other constructor failures.
+            let revert = RevertOutput::new(exec_status.output_data);
+            Err(revert.into_actor_error())
         } else if exec_status.status_code == StatusCode::Success {
             if exec_status.output_data.is_empty() {
                 return Err(ActorError::unspecified(
@@ -112,6 +281,7 @@ impl EvmContractActor {
             // the resulting bytecode.
             let contract_bytecode = exec_status.output_data;
 
+            let logs = system.take_logs();
             let contract_state_cid = system.flush_state()?;
 
             let state = State::new(
@@ -123,6 +293,11 @@ impl EvmContractActor {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to construct state")
             })?;
             rt.create(&state)?;
+            increment_blob_ref(rt, state.bytecode);
+
+            for log in logs {
+                rt.emit_event(&log_to_actor_event(&log))?;
+            }
 
             Ok(())
         } else if let StatusCode::ActorError(e) = exec_status.status_code {
@@ -165,32 +340,45 @@ impl EvmContractActor {
                     ))
                 })?;
 
-        let mut system = System::new(rt, &mut kamt).map_err(|e| {
+        let mut storage = Storage::default();
+        let mut system = System::new(rt, &mut kamt, &mut storage).map_err(|e| {
             ActorError::unspecified(format!("failed to create execution abstraction layer: {e:?}"))
         })?;
 
         let mut exec_state = ExecutionState::new(method, input_data.to_vec().into());
 
+        let snapshot = system.snapshot();
+
         let exec_status =
             execute(&bytecode, &mut exec_state, &mut system.reborrow()).map_err(|e| match e {
                 StatusCode::ActorError(e) => e,
                 _ => ActorError::unspecified(format!("EVM execution error: {e:?}")),
             })?;
 
-        // TODO this does not return revert data yet, but it has correct semantics.
-        if exec_status.reverted {
-            return Err(ActorError::unchecked(
-                EVM_CONTRACT_REVERTED,
-                "contract reverted".to_string(),
-            ));
+        let logs = if exec_status.reverted {
+            // Undo any storage writes this invocation made before reverting;
+            // transient storage is simply dropped with `storage` below.
+            system.rollback(snapshot);
+            // Same as above: forward the revert buffer rather than collapsing
+            // it into an opaque exit code.
+            let revert = RevertOutput::new(exec_status.output_data);
+            return Err(revert.into_actor_error());
         } else if exec_status.status_code == StatusCode::Success {
             // this needs to be outside the transaction or else rustc has a fit about
             // mutably borrowing the runtime twice.... sigh.
+            let logs = system.take_logs();
             let contract_state = system.flush_state()?;
             rt.transaction(|state: &mut State, _rt| {
                 state.contract_state = contract_state;
                 Ok(())
             })?;
+
+            // Emit each LOG0-LOG4 as an FVM actor event so indexers can
+            // reconstruct the contract's Solidity event stream.
+            for log in &logs {
+                rt.emit_event(&log_to_actor_event(log))?;
+            }
+            logs
         } else if let StatusCode::ActorError(e) = exec_status.status_code {
             return Err(e);
         } else {
@@ -198,14 +386,21 @@ impl EvmContractActor {
                 "EVM contract invocation failed: status: {}",
                 exec_status.status_code
             )));
-        }
+        };
 
         if let Some(addr) = exec_status.selfdestroyed {
+            // Drop this contract's reference to its bytecode blob before
+            // deleting the actor -- once it's gone there is no `state` left
+            // to read `bytecode` off of.
+            decrement_blob_ref(rt, state.bytecode);
             rt.delete_actor(&addr)?
         }
 
-        let output = RawBytes::from(exec_status.output_data.to_vec());
-        Ok(output)
+        let receipt = InvokeContractReturn {
+            output_data: RawBytes::from(exec_status.output_data.to_vec()),
+            logs: logs.iter().map(ReceiptLog::from).collect(),
+        };
+        Ok(RawBytes::serialize(receipt)?)
     }
 
     pub fn bytecode<BS, RT>(rt: &mut RT) -> Result<Cid, ActorError>
@@ -220,6 +415,57 @@ impl EvmContractActor {
         Ok(state.bytecode)
     }
 
+    /// Store `params.bytecode` content-addressed ahead of deploying any
+    /// contract, returning its CID. Pass that CID back as `bytecode` in a
+    /// `ConstructorParams` with `deploy_as_blob_cid` set to deploy many
+    /// contracts that share the same runtime code -- e.g. clones of a
+    /// minimal proxy -- without re-uploading or re-executing identical init
+    /// code for every instance.
+    pub fn upload_bytecode<BS, RT>(
+        rt: &mut RT,
+        params: UploadBytecodeParams,
+    ) -> Result<Cid, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.bytecode.len() > MAX_CODE_SIZE {
+            return Err(ActorError::illegal_argument(format!(
+                "EVM byte code length ({}) is exceeding the maximum allowed of {MAX_CODE_SIZE}",
+                params.bytecode.len()
+            )));
+        }
+        if params.bytecode.is_empty() {
+            return Err(ActorError::illegal_argument("no bytecode provided".into()));
+        }
+
+        state::put_bytecode_blob(rt.store(), &params.bytecode)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to store bytecode blob"))
+    }
+
+    /// Resolve a bytecode blob CID -- this contract's own, a previously
+    /// uploaded one, or any other contract's -- to its raw bytes.
+    pub fn get_bytecode_blob<BS, RT>(
+        rt: &mut RT,
+        params: GetBytecodeBlobParams,
+    ) -> Result<RawBytes, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let bytes: Vec<u8> = rt
+            .store()
+            .get(&params.bytecode)
+            .map_err(|e| ActorError::unspecified(format!("failed to load bytecode blob: {e:?}")))?
+            .ok_or_else(|| ActorError::not_found("bytecode blob not found".to_string()))?;
+
+        Ok(RawBytes::new(bytes))
+    }
+
     pub fn storage_at<BS, RT>(rt: &mut RT, params: GetStorageAtParams) -> Result<U256, ActorError>
     where
         BS: Blockstore + Clone,
@@ -241,7 +487,8 @@ impl EvmContractActor {
                     ))
                 })?;
 
-        let mut system = System::new(rt, &mut kamt).map_err(|e| {
+        let mut storage = Storage::default();
+        let mut system = System::new(rt, &mut kamt, &mut storage).map_err(|e| {
             ActorError::unspecified(format!("failed to create execution abstraction layer: {e:?}"))
         })?;
 
@@ -278,6 +525,13 @@ impl ActorCode for EvmContractActor {
                 let value = Self::storage_at(rt, cbor::deserialize_params(params)?)?;
                 Ok(RawBytes::serialize(value)?)
             }
+            Some(Method::UploadBytecode) => {
+                let cid = Self::upload_bytecode(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(cid)?)
+            }
+            Some(Method::GetBytecodeBlob) => {
+                Self::get_bytecode_blob(rt, cbor::deserialize_params(params)?)
+            }
             None => Self::invoke_contract(rt, method, params),
         }
     }
@@ -285,7 +539,26 @@ impl ActorCode for EvmContractActor {
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
+    /// Init code to execute (whose return value becomes the deployed
+    /// runtime bytecode), or -- when `deploy_as_blob_cid` is set -- the
+    /// serialized CID of runtime code already uploaded via
+    /// `UploadBytecode`.
     pub bytecode: RawBytes,
+    /// Skip init-code execution and deploy `bytecode` (read as a blob CID)
+    /// directly. Lets factory/proxy deployers reuse one uploaded copy of
+    /// identical runtime code across many contract instances.
+    #[serde(default)]
+    pub deploy_as_blob_cid: bool,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct UploadBytecodeParams {
+    pub bytecode: RawBytes,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetBytecodeBlobParams {
+    pub bytecode: Cid,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
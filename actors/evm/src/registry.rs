@@ -0,0 +1,187 @@
+//! A tiny singleton actor that tracks how many deployed contracts
+//! reference each content-addressed bytecode blob (see
+//! [`crate::state::put_bytecode_blob`]), so a blob can be reclaimed once
+//! its last referencing contract self-destructs.
+//!
+//! `EvmContractActor` instances are independent actors with independent
+//! state, so a refcount kept on one contract's own `State` is invisible to
+//! every other contract sharing the same blob (e.g. many clones of the
+//! same proxy deployed via `deploy_as_blob_cid`). Making the count
+//! actually shared needs one piece of state every instance can reach, the
+//! same way other FVM singletons (the system actor at ID 0, the init
+//! actor at ID 1) are reached at a fixed, well-known ID rather than looked
+//! up.
+
+use {
+    cid::Cid,
+    fil_actors_runtime::{
+        cbor,
+        runtime::{ActorCode, Runtime},
+        ActorError,
+    },
+    fvm_ipld_blockstore::Blockstore,
+    fvm_ipld_encoding::tuple::*,
+    fvm_ipld_encoding::RawBytes,
+    fvm_ipld_kamt::Kamt,
+    fvm_shared::{MethodNum, METHOD_CONSTRUCTOR},
+    num_derive::FromPrimitive,
+    num_traits::FromPrimitive,
+};
+
+/// The well-known actor ID `EvmContractActor` sends every blob
+/// reference-count change to. Chosen above the IDs this actors bundle
+/// reserves for fixed-ID singletons (such as the system actor at `0` and
+/// the init actor at `1`); bump it if a later addition claims `103` first.
+///
+/// Nothing in this crate creates the singleton at this ID -- that has to
+/// happen once, out of band, as part of the network's genesis/actor
+/// manifest, the same way the system and init actors are bootstrapped.
+/// Until that's wired up, `notify_blob_registry` sending here is a no-op:
+/// see its doc comment for why that's safe.
+pub const BYTECODE_REGISTRY_ACTOR_ID: u64 = 103;
+
+#[derive(FromPrimitive)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    IncrementRef = 2,
+    DecrementRef = 3,
+}
+
+/// Root of a `Cid -> u64` Kamt counting live references to each
+/// content-addressed bytecode blob. Absent entries (including a blob that
+/// was never referenced, or one whose count has been decremented back to
+/// zero and dropped) are implicitly zero.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, Default)]
+pub struct State {
+    pub refs: Option<Cid>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct RefParams {
+    pub blob: Cid,
+}
+
+pub struct BytecodeRegistryActor;
+
+impl BytecodeRegistryActor {
+    pub fn constructor<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        rt.create(&State::default())
+    }
+
+    /// Record one more live reference to `params.blob`, returning the new
+    /// count. Called by `EvmContractActor::constructor` once per deployed
+    /// contract, whether it ran init code or was deployed by reference.
+    pub fn increment_ref<BS, RT>(rt: &mut RT, params: RefParams) -> Result<u64, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let mut count = 0u64;
+        rt.transaction(|state: &mut State, rt| {
+            let store = rt.store();
+            let mut kamt: Kamt<BS, Cid, u64> = load_or_init(&state.refs, store.clone())?;
+            count = kamt
+                .get(&params.blob)
+                .map_err(|e| ActorError::illegal_state(format!("failed to read refcount: {e:?}")))?
+                .copied()
+                .unwrap_or(0)
+                + 1;
+            kamt.set(params.blob, count)
+                .map_err(|e| ActorError::illegal_state(format!("failed to write refcount: {e:?}")))?;
+            state.refs = Some(
+                kamt.flush()
+                    .map_err(|e| ActorError::illegal_state(format!("failed to flush refcount kamt: {e:?}")))?,
+            );
+            Ok(())
+        })?;
+
+        Ok(count)
+    }
+
+    /// Drop one live reference to `params.blob`, returning the remaining
+    /// count. A return of `0` means the blob is no longer referenced by any
+    /// contract and its entry has been removed from the registry -- the
+    /// blob itself is reclaimed the same way any other unreferenced block
+    /// is, by the blockstore's own garbage collection once no actor state
+    /// root points to it any more.
+    pub fn decrement_ref<BS, RT>(rt: &mut RT, params: RefParams) -> Result<u64, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let mut count = 0u64;
+        rt.transaction(|state: &mut State, rt| {
+            let store = rt.store();
+            let mut kamt: Kamt<BS, Cid, u64> = load_or_init(&state.refs, store.clone())?;
+            let current = kamt
+                .get(&params.blob)
+                .map_err(|e| ActorError::illegal_state(format!("failed to read refcount: {e:?}")))?
+                .copied()
+                .unwrap_or(0);
+            count = current.saturating_sub(1);
+            if count == 0 {
+                kamt.delete(&params.blob)
+                    .map_err(|e| ActorError::illegal_state(format!("failed to drop refcount entry: {e:?}")))?;
+            } else {
+                kamt.set(params.blob, count)
+                    .map_err(|e| ActorError::illegal_state(format!("failed to write refcount: {e:?}")))?;
+            }
+            state.refs = Some(
+                kamt.flush()
+                    .map_err(|e| ActorError::illegal_state(format!("failed to flush refcount kamt: {e:?}")))?,
+            );
+            Ok(())
+        })?;
+
+        Ok(count)
+    }
+}
+
+fn load_or_init<BS: Blockstore + Clone>(
+    root: &Option<Cid>,
+    store: BS,
+) -> Result<Kamt<BS, Cid, u64>, ActorError> {
+    match root {
+        Some(cid) => Kamt::load(cid, store)
+            .map_err(|e| ActorError::illegal_state(format!("failed to load refcount kamt: {e:?}"))),
+        None => Ok(Kamt::new(store)),
+    }
+}
+
+impl ActorCode for BytecodeRegistryActor {
+    fn invoke_method<BS, RT>(
+        rt: &mut RT,
+        method: MethodNum,
+        params: &RawBytes,
+    ) -> Result<RawBytes, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        match FromPrimitive::from_u64(method) {
+            Some(Method::Constructor) => {
+                Self::constructor(rt)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::IncrementRef) => {
+                let count = Self::increment_ref(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(count)?)
+            }
+            Some(Method::DecrementRef) => {
+                let count = Self::decrement_ref(rt, cbor::deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(count)?)
+            }
+            None => Err(ActorError::unspecified(format!("unknown method {method}"))),
+        }
+    }
+}
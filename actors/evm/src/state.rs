@@ -0,0 +1,33 @@
+use cid::{multihash::Code, Cid};
+use fvm_ipld_blockstore::{Block, Blockstore};
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{RawBytes, DAG_CBOR};
+
+/// Data stored by an actor representing an EVM smart contract.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct State {
+    /// CID of the contract's runtime bytecode.
+    pub bytecode: Cid,
+    /// CID of the root of the contract's storage Kamt.
+    pub contract_state: Cid,
+}
+
+impl State {
+    pub fn new<BS: Blockstore>(
+        store: &BS,
+        bytecode: RawBytes,
+        contract_state: Cid,
+    ) -> anyhow::Result<Self> {
+        let bytecode_cid = put_bytecode_blob(store, &bytecode)?;
+        Ok(Self { bytecode: bytecode_cid, contract_state })
+    }
+}
+
+/// Store `bytecode` content-addressed and return its CID. Because the CID
+/// is derived from the bytecode's own hash, two contracts deployed with
+/// byte-identical runtime code (a common outcome of factory and proxy
+/// patterns) land on the same key and share the same underlying block
+/// instead of each paying to store their own 24kB copy.
+pub fn put_bytecode_blob<BS: Blockstore>(store: &BS, bytecode: &[u8]) -> anyhow::Result<Cid> {
+    Ok(store.put(Code::Blake2b256, &Block { codec: DAG_CBOR, data: bytecode.to_vec() })?)
+}